@@ -1,10 +1,14 @@
+use async_trait::async_trait;
 use serde::{Serialize, Deserialize};
 use sha2::{Sha256, Digest};
-use std::time::{SystemTime, UNIX_EPOCH};
-use reqwest;
-use hex;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use anyhow::{Result, Context};
+use tokio::sync::mpsc;
 use tokio::task;
+use tokio::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Block {
@@ -14,10 +18,11 @@ pub struct Block {
     pub previous_hash: String,
     pub hash: String,
     pub nonce: u64,
+    pub difficulty: u32,
 }
 
 impl Block {
-    pub fn new(index: u64, data: Vec<u8>, previous_hash: String) -> Self {
+    pub fn new(index: u64, data: Vec<u8>, previous_hash: String, difficulty: u32) -> Self {
         let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs();
         let mut block = Block {
             index,
@@ -26,6 +31,7 @@ impl Block {
             previous_hash,
             hash: String::new(),
             nonce: 0,
+            difficulty,
         };
         block.hash = block.calculate_hash();
         block
@@ -39,122 +45,589 @@ impl Block {
     }
 }
 
-fn meets_difficulty(hash: &str, difficulty: u32) -> bool {
-    let target = vec![0u8; difficulty as usize];
-    let hash_bytes = hex::decode(hash).expect("Hex decode failed");
-    hash_bytes.starts_with(&target)
+/// A candidate view of the chain, tracking the accumulated proof-of-work behind it so
+/// competing tips can be compared without re-walking the whole block list each time.
+#[derive(Debug, Clone)]
+pub struct Chain {
+    pub blocks: Vec<Block>,
+    pub total_difficulty: u128,
 }
 
-async fn mine_block(previous_block: &Block, data: Vec<u8>, difficulty: u32) -> Block {
-    let mut new_block = Block::new(previous_block.index + 1, data.clone(), previous_block.hash.clone());
+impl Chain {
+    pub fn new(blocks: Vec<Block>) -> Self {
+        // `difficulty` is a bit count and can't exceed a sha256 hash's 256 bits, but
+        // saturate rather than panic if a peer ever reports something absurd.
+        let total_difficulty = blocks.iter()
+            .fold(0u128, |total, block| total.saturating_add(2u128.saturating_pow(block.difficulty)));
+        Chain { blocks, total_difficulty }
+    }
 
-    // Parallel mining using multiple threads
-    let mining_task = task::spawn_blocking(move || {
-        let mut attempt = 0;
-        while !meets_difficulty(&new_block.hash, difficulty) {
-            new_block.nonce += 1;
-            new_block.hash = new_block.calculate_hash();
+    pub fn tip(&self) -> Option<&Block> {
+        self.blocks.last()
+    }
 
-            attempt += 1;
-            if attempt % 100 == 0 {
-                println!("Attempt {}: Trying hash: {}", attempt, new_block.hash);
+    /// Checks that every block links to its predecessor, that its stored hash matches
+    /// its contents, and that the hash actually satisfies its claimed difficulty —
+    /// rejecting the chain if a malicious server has fed us an invalid tip.
+    pub fn validate(&self) -> Result<()> {
+        for (i, block) in self.blocks.iter().enumerate() {
+            if i > 0 && block.previous_hash != self.blocks[i - 1].hash {
+                return Err(anyhow::anyhow!(
+                    "block {} does not link to its predecessor",
+                    block.index
+                ));
+            }
+
+            if block.hash != block.calculate_hash() {
+                return Err(anyhow::anyhow!("block {} hash does not match its contents", block.index));
+            }
+
+            if !meets_difficulty(&block.hash, block.difficulty).unwrap_or(false) {
+                return Err(anyhow::anyhow!(
+                    "block {} does not meet its claimed difficulty",
+                    block.index
+                ));
             }
         }
 
-        new_block
-    });
+        Ok(())
+    }
+}
 
-    mining_task.await.unwrap()
+/// Picks the heavier of two chains, breaking ties by lower tip timestamp then by
+/// lexicographically smaller tip hash.
+pub fn fork_choice<'a>(a: &'a Chain, b: &'a Chain) -> &'a Chain {
+    use std::cmp::Ordering;
+
+    match a.total_difficulty.cmp(&b.total_difficulty) {
+        Ordering::Greater => return a,
+        Ordering::Less => return b,
+        Ordering::Equal => {}
+    }
+
+    match (a.tip(), b.tip()) {
+        (Some(a_tip), Some(b_tip)) => match a_tip.timestamp.cmp(&b_tip.timestamp) {
+            Ordering::Less => a,
+            Ordering::Greater => b,
+            Ordering::Equal => if a_tip.hash <= b_tip.hash { a } else { b },
+        },
+        (Some(_), None) => a,
+        (None, Some(_)) => b,
+        (None, None) => a,
+    }
 }
 
-async fn get_last_block_from_server() -> Result<Block> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:8000/last-block";
+/// `difficulty` is a count of required leading zero bits, not whole bytes.
+fn meets_difficulty(hash: &str, difficulty: u32) -> Result<bool> {
+    let hash_bytes = hex::decode(hash).context("Hex decode failed")?;
 
-    let res = client.get(url)
-        .send()
-        .await
-        .context("Failed to send request to get last block")?;
+    let full_bytes = (difficulty / 8) as usize;
+    let rem_bits = difficulty % 8;
 
-    let last_block: Block = res.json().await
-        .context("Failed to parse last block")?;
-    Ok(last_block)
+    if hash_bytes.len() < full_bytes {
+        return Ok(false);
+    }
+    if hash_bytes[..full_bytes].iter().any(|&byte| byte != 0) {
+        return Ok(false);
+    }
+
+    if rem_bits == 0 {
+        return Ok(true);
+    }
+
+    match hash_bytes.get(full_bytes) {
+        Some(&byte) => Ok(byte < (1u8 << (8 - rem_bits))),
+        None => Ok(false),
+    }
+}
+
+/// A non-2xx response to a submitted block, carrying the status and body separately so
+/// callers (e.g. `MiningEvent::BlockRejected`) can report the real status instead of a
+/// flattened string.
+#[derive(Debug)]
+pub struct SubmitError {
+    pub status: String,
+    pub body: String,
 }
 
-async fn send_block_to_server(block: &Block) -> Result<()> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:8000/new-block";
+impl std::fmt::Display for SubmitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} - {}", self.status, self.body)
+    }
+}
 
-    println!("Sending block: {:?}", block);
+impl std::error::Error for SubmitError {}
 
-    let res = client.post(url)
-        .json(block)
-        .send()
-        .await
-        .context("Failed to send request to post new block")?;
+/// Lifecycle events a miner can report as it works, so callers can drive a dashboard,
+/// metrics, or logging without this crate owning the output format.
+#[derive(Debug, Clone)]
+pub enum MiningEvent {
+    AttemptProgress { nonce: u64, hash: String },
+    BlockMined { index: u64, nonce: u64, elapsed_ms: u64 },
+    BlockAccepted,
+    BlockRejected { status: String, body: String },
+    StaleTipDetected,
+    InvalidTipRejected { reason: String },
+    DifficultyChanged { old: u32, new: u32 },
+}
 
-    let status = res.status();
-    let body = res.text().await
-        .context("Failed to read response text")?;
+/// An event sink paired with the timestamp (micros since the epoch) it fired at.
+/// `None` means no one is listening, so callers pay nothing beyond this check.
+type EventSender = Option<mpsc::Sender<(MiningEvent, u64)>>;
 
-    if status.is_success() {
-        println!("Block successfully sent to server.");
-    } else {
-        println!("Failed to send block to server: {} - {}", status, body);
+fn emit(events: &EventSender, event: MiningEvent) {
+    if let Some(tx) = events {
+        let now_micros = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_micros() as u64;
+        let _ = tx.try_send((event, now_micros));
+    }
+}
+
+/// Number of trailing blocks a retarget looks back over.
+const RETARGET_WINDOW: usize = 10;
+/// Desired average seconds between blocks.
+const TARGET_BLOCK_SECS: u64 = 15;
+const MIN_DIFFICULTY: u32 = 1;
+/// A sha256 hash is 256 bits wide, so that's the ceiling on meaningful difficulty.
+const MAX_DIFFICULTY: u32 = 256;
+
+/// Adjusts `current` so block production converges on `TARGET_BLOCK_SECS`. Compares the
+/// time actually taken to produce the last `RETARGET_WINDOW` blocks against the expected
+/// time, and nudges difficulty by at most one zero-bit per call (since difficulty is now
+/// measured in bits, each unit is a 2x change in expected work).
+fn retarget(history: &[Block], current: u32) -> u32 {
+    if history.len() <= RETARGET_WINDOW {
+        return current;
+    }
+
+    let newest = history.last().expect("history.len() > RETARGET_WINDOW checked above");
+    let oldest = &history[history.len() - 1 - RETARGET_WINDOW];
+
+    let actual = newest.timestamp.saturating_sub(oldest.timestamp).max(1) as f64;
+    let expected = (RETARGET_WINDOW as u64 * TARGET_BLOCK_SECS) as f64;
+
+    let adjustment = (expected / actual).log2().round().clamp(-1.0, 1.0) as i64;
+    let new_difficulty = current as i64 + adjustment;
+
+    new_difficulty.clamp(MIN_DIFFICULTY as i64, MAX_DIFFICULTY as i64) as u32
+}
+
+async fn mine_block(previous_block: &Block, data: Vec<u8>, difficulty: u32, cancel: CancellationToken, source: Arc<dyn BlockSource>, events: EventSender, worker_count: Option<u64>) -> Option<Block> {
+    let start = Instant::now();
+
+    // The block template (index, timestamp, data, previous_hash) is fixed once up
+    // front so every worker is racing over the same header, only the nonce varies.
+    let template = Block::new(previous_block.index + 1, data, previous_block.hash.clone(), difficulty);
+
+    let worker_count = worker_count.unwrap_or_else(|| num_cpus::get().max(1) as u64).max(1);
+    let found = Arc::new(AtomicBool::new(false));
+    let winning_nonce = Arc::new(AtomicU64::new(0));
+
+    // Poll the server for a newer tip while we mine; if one lands, cancel the token so
+    // mining aborts instead of wasting work finishing a now-stale block.
+    let poll_handle = {
+        let cancel = cancel.clone();
+        let source = Arc::clone(&source);
+        let events = events.clone();
+        let previous_index = previous_block.index;
+        task::spawn(async move {
+            while !cancel.is_cancelled() {
+                tokio::select! {
+                    _ = cancel.cancelled() => break,
+                    _ = tokio::time::sleep(Duration::from_millis(500)) => {}
+                }
+
+                if let Ok(latest) = source.last_block().await {
+                    if latest.index > previous_index {
+                        emit(&events, MiningEvent::StaleTipDetected);
+                        cancel.cancel();
+                        break;
+                    }
+                }
+            }
+        })
+    };
+
+    // Partition the nonce space into disjoint strides: worker `k` of `worker_count`
+    // only ever tries k, k + worker_count, k + 2*worker_count, ...
+    let mut workers = Vec::with_capacity(worker_count as usize);
+    for worker_id in 0..worker_count {
+        let mut block = template.clone();
+        let found = Arc::clone(&found);
+        let winning_nonce = Arc::clone(&winning_nonce);
+        let cancel = cancel.clone();
+        let events = events.clone();
+
+        workers.push(task::spawn_blocking(move || {
+            block.nonce = worker_id;
+            let mut attempt: u64 = 0;
+
+            loop {
+                block.hash = block.calculate_hash();
+                if meets_difficulty(&block.hash, difficulty).unwrap_or(false) {
+                    winning_nonce.store(block.nonce, Ordering::SeqCst);
+                    found.store(true, Ordering::SeqCst);
+                    return;
+                }
+
+                attempt += 1;
+                if attempt.is_multiple_of(4096) {
+                    if found.load(Ordering::SeqCst) || cancel.is_cancelled() {
+                        return;
+                    }
+                    emit(&events, MiningEvent::AttemptProgress { nonce: block.nonce, hash: block.hash.clone() });
+                }
+
+                block.nonce += worker_count;
+            }
+        }));
+    }
+
+    for worker in workers {
+        worker.await.unwrap();
     }
 
-    Ok(())
+    // Mining finished, one way or another: a winner was found, or the tip moved out
+    // from under us. Either way the stale-tip poller no longer needs to run.
+    cancel.cancel();
+    poll_handle.abort();
+
+    if !found.load(Ordering::SeqCst) {
+        return None;
+    }
+
+    let mut winning_block = template;
+    winning_block.nonce = winning_nonce.load(Ordering::SeqCst);
+    winning_block.hash = winning_block.calculate_hash();
+
+    emit(&events, MiningEvent::BlockMined {
+        index: winning_block.index,
+        nonce: winning_block.nonce,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+    });
+
+    Some(winning_block)
+}
+
+/// A backend a miner can fetch chain state from and submit mined blocks to. Letting
+/// this be a trait object means `main` isn't wedded to talking HTTP to one hardcoded
+/// host, and lets `MultiSource` below fan a single miner out across several nodes.
+#[async_trait]
+pub trait BlockSource: Send + Sync {
+    async fn last_block(&self) -> Result<Block>;
+    async fn difficulty(&self) -> Result<u32>;
+    async fn submit(&self, block: &Block) -> Result<()>;
+}
+
+/// Talks to a single node's HTTP API.
+pub struct HttpBlockSource {
+    base_url: String,
+}
+
+impl HttpBlockSource {
+    pub fn new(base_url: impl Into<String>) -> Self {
+        HttpBlockSource { base_url: base_url.into() }
+    }
 }
 
-async fn get_difficulty_from_server() -> Result<u32> {
-    let client = reqwest::Client::new();
-    let url = "http://localhost:8000/difficulty";
+#[async_trait]
+impl BlockSource for HttpBlockSource {
+    async fn last_block(&self) -> Result<Block> {
+        let client = reqwest::Client::new();
+        let url = format!("{}/last-block", self.base_url);
+
+        let res = client.get(&url)
+            .send()
+            .await
+            .context("Failed to send request to get last block")?;
+
+        let last_block: Block = res.json().await
+            .context("Failed to parse last block")?;
+        Ok(last_block)
+    }
+
+    async fn difficulty(&self) -> Result<u32> {
+        let client = reqwest::Client::new();
+        let url = format!("{}/difficulty", self.base_url);
 
-    let res = client.get(url)
-        .send()
-        .await
-        .context("Failed to send request to get difficulty")?;
+        let res = client.get(&url)
+            .send()
+            .await
+            .context("Failed to send request to get difficulty")?;
 
-    let difficulty_str = res.text().await
-        .context("Failed to read difficulty response")?;
-    let difficulty = difficulty_str.trim_start_matches("Difficulty: ")
-        .parse::<u32>()
-        .context("Failed to parse difficulty")?;
+        let difficulty_str = res.text().await
+            .context("Failed to read difficulty response")?;
+        let difficulty = difficulty_str.trim_start_matches("Difficulty: ")
+            .parse::<u32>()
+            .context("Failed to parse difficulty")?;
 
-    Ok(difficulty)
+        Ok(difficulty)
+    }
+
+    async fn submit(&self, block: &Block) -> Result<()> {
+        let client = reqwest::Client::new();
+        let url = format!("{}/new-block", self.base_url);
+
+        let res = client.post(&url)
+            .json(block)
+            .send()
+            .await
+            .context("Failed to send request to post new block")?;
+
+        let status = res.status();
+        if status.is_success() {
+            return Ok(());
+        }
+
+        let body = res.text().await
+            .context("Failed to read response text")?;
+        Err(SubmitError { status: status.to_string(), body }.into())
+    }
 }
 
-async fn display_difficulty() -> Result<()> {
-    let difficulty = get_difficulty_from_server().await
-        .context("Error retrieving difficulty from server")?;
+/// Queries backends in order, falling back to the next on error. For `last_block` it
+/// queries every backend and keeps the highest tip, since a lagging node shouldn't be
+/// allowed to hand back a stale chain just because it answered first.
+pub struct MultiSource {
+    sources: Vec<Box<dyn BlockSource>>,
+}
 
-    println!("Current Difficulty: {}", difficulty);
+impl MultiSource {
+    pub fn new(sources: Vec<Box<dyn BlockSource>>) -> Self {
+        MultiSource { sources }
+    }
+}
+
+#[async_trait]
+impl BlockSource for MultiSource {
+    async fn last_block(&self) -> Result<Block> {
+        let mut best: Option<Chain> = None;
+        let mut last_err = None;
+
+        for source in &self.sources {
+            match source.last_block().await {
+                Ok(block) => {
+                    let candidate = Chain::new(vec![block]);
+                    best = Some(match best.take() {
+                        Some(current) => {
+                            // The higher index wins outright; only tips at the same
+                            // height go to `fork_choice`.
+                            let current_index = current.tip().map(|tip| tip.index);
+                            let candidate_index = candidate.tip().map(|tip| tip.index);
+                            match candidate_index.cmp(&current_index) {
+                                std::cmp::Ordering::Greater => candidate,
+                                std::cmp::Ordering::Less => current,
+                                std::cmp::Ordering::Equal => fork_choice(&current, &candidate).clone(),
+                            }
+                        }
+                        None => candidate,
+                    });
+                }
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        best.and_then(|chain| chain.tip().cloned())
+            .ok_or_else(|| last_err.unwrap_or_else(|| anyhow::anyhow!("no block sources available")))
+    }
 
-    Ok(())
+    async fn difficulty(&self) -> Result<u32> {
+        let mut last_err = None;
+        for source in &self.sources {
+            match source.difficulty().await {
+                Ok(difficulty) => return Ok(difficulty),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no block sources available")))
+    }
+
+    async fn submit(&self, block: &Block) -> Result<()> {
+        let mut last_err = None;
+        for source in &self.sources {
+            match source.submit(block).await {
+                Ok(()) => return Ok(()),
+                Err(err) => last_err = Some(err),
+            }
+        }
+
+        Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no block sources available")))
+    }
 }
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    loop {
-        // Anzeige der aktuellen Schwierigkeit
-        display_difficulty().await
-            .context("Error displaying difficulty")?;
+    // Any number of server URLs can be passed on the command line so the miner can
+    // fall back to another node if one is unreachable; defaults to the local server.
+    // `--workers=N` overrides the default of one worker per CPU core.
+    let mut server_urls: Vec<String> = Vec::new();
+    let mut worker_count: Option<u64> = None;
+    for arg in std::env::args().skip(1) {
+        match arg.strip_prefix("--workers=") {
+            Some(count) => worker_count = Some(count.parse().context("Invalid --workers value")?),
+            None => server_urls.push(arg),
+        }
+    }
+    let server_urls = if server_urls.is_empty() {
+        vec!["http://localhost:8000".to_string()]
+    } else {
+        server_urls
+    };
+
+    let source: Arc<dyn BlockSource> = Arc::new(MultiSource::new(
+        server_urls.into_iter()
+            .map(|url| Box::new(HttpBlockSource::new(url)) as Box<dyn BlockSource>)
+            .collect(),
+    ));
+
+    // Drive the console output off the event stream instead of sprinkling println!
+    // through the mining/networking code, so another caller could swap in a different
+    // sink (a dashboard, metrics, ...) without touching this crate.
+    let (event_tx, mut event_rx) = mpsc::channel::<(MiningEvent, u64)>(256);
+    task::spawn(async move {
+        while let Some((event, _timestamp_micros)) = event_rx.recv().await {
+            match event {
+                MiningEvent::AttemptProgress { nonce, hash } => {
+                    println!("Trying nonce {}: {}", nonce, hash);
+                }
+                MiningEvent::BlockMined { index, nonce, elapsed_ms } => {
+                    println!("Mined block {} with nonce {} in {}ms", index, nonce, elapsed_ms);
+                }
+                MiningEvent::BlockAccepted => println!("Block successfully sent to server."),
+                MiningEvent::BlockRejected { status, body } => {
+                    println!("Failed to send block to server: {} - {}", status, body);
+                }
+                MiningEvent::StaleTipDetected => {
+                    println!("Stale tip detected while mining, re-fetching latest block.");
+                }
+                MiningEvent::InvalidTipRejected { reason } => {
+                    println!("Rejected server's reported tip: {}", reason);
+                }
+                MiningEvent::DifficultyChanged { old, new } => {
+                    println!("Difficulty changed: {} -> {}", old, new);
+                }
+            }
+        }
+    });
+    let events: EventSender = Some(event_tx);
+
+    let mut last_difficulty: Option<u32> = None;
+    // Our own view of the chain, built up from validated tips as we go. Bounded to the
+    // retargeting window since that's all `retarget` and continuity checks need.
+    let mut chain_history: Vec<Block> = Vec::new();
 
+    loop {
         // Hol die Schwierigkeit vom Server
-        let difficulty = get_difficulty_from_server().await
+        let server_difficulty = source.difficulty().await
             .context("Error retrieving difficulty from server")?;
 
         // Hol den letzten Block vom Server
-        let previous_block = get_last_block_from_server().await
+        let fetched_tip = source.last_block().await
             .context("Error retrieving last block from server")?;
 
+        // If the fetched tip doesn't continue what we already validated (a race with
+        // another miner, or the server jumping to a different fork), start our local
+        // view over from just that tip rather than pretending it links to our history.
+        // A tip we already hold (e.g. the block we just submitted) is kept as is.
+        let already_held = chain_history.last().is_some_and(|tip| fetched_tip.hash == tip.hash);
+        let linked = chain_history.last().is_some_and(|tip| fetched_tip.previous_hash == tip.hash);
+        if !linked && !already_held {
+            chain_history.clear();
+        }
+        if !already_held {
+            chain_history.push(fetched_tip.clone());
+        }
+
+        let candidate_chain = Chain::new(chain_history.clone());
+        if let Err(err) = candidate_chain.validate() {
+            // A single bad/forked response shouldn't take the whole miner down; reject
+            // this tip, drop it from our local view, and go back around to re-fetch.
+            chain_history.pop();
+            emit(&events, MiningEvent::InvalidTipRejected { reason: err.to_string() });
+            continue;
+        }
+
+        while chain_history.len() > RETARGET_WINDOW + 1 {
+            chain_history.remove(0);
+        }
+
+        // Let our own observed block times nudge difficulty instead of being wholly
+        // dependent on the server-supplied value. Each retarget builds on our own prior
+        // decision (falling back to the server's value only on the very first loop), so
+        // difficulty can keep drifting as long as block times keep diverging instead of
+        // being capped within ±1 of whatever the server says.
+        let difficulty = retarget(&chain_history, last_difficulty.unwrap_or(server_difficulty));
+        if let Some(old) = last_difficulty {
+            if old != difficulty {
+                emit(&events, MiningEvent::DifficultyChanged { old, new: difficulty });
+            }
+        }
+        last_difficulty = Some(difficulty);
+
+        let previous_block = fetched_tip;
         let data = b"Block data".to_vec();
-        let new_block = mine_block(&previous_block, data, difficulty).await;
+        let cancel = CancellationToken::new();
+        let new_block = match mine_block(&previous_block, data, difficulty, cancel, Arc::clone(&source), events.clone(), worker_count).await {
+            Some(block) => block,
+            None => {
+                // Someone else's block landed while we were mining; re-fetch the new
+                // tip instead of submitting a block that's now stale.
+                continue;
+            }
+        };
 
-        send_block_to_server(&new_block).await
-            .context("Error sending block to server")?;
+        match source.submit(&new_block).await {
+            Ok(()) => {
+                emit(&events, MiningEvent::BlockAccepted);
+                chain_history.push(new_block);
+            }
+            Err(err) => {
+                let (status, body) = match err.downcast_ref::<SubmitError>() {
+                    Some(submit_err) => (submit_err.status.clone(), submit_err.body.clone()),
+                    None => ("send failed".to_string(), err.to_string()),
+                };
+                emit(&events, MiningEvent::BlockRejected { status, body });
+            }
+        }
 
         // Warte eine gewisse Zeit, bevor der nächste Block erstellt wird
-        tokio::time::sleep(tokio::time::Duration::from_secs(0)).await; // Wartezeit erhöht
+        tokio::time::sleep(Duration::from_secs(0)).await; // Wartezeit erhöht
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_at(index: u64, timestamp: u64) -> Block {
+        Block {
+            index,
+            timestamp,
+            data: vec![],
+            previous_hash: String::new(),
+            hash: String::new(),
+            nonce: 0,
+            difficulty: 0,
+        }
+    }
+
+    #[test]
+    fn retarget_raises_difficulty_when_blocks_come_too_fast() {
+        // 10 blocks in 10 seconds against a 15s/block target: blocks are coming in fast.
+        let history: Vec<Block> = (0..=RETARGET_WINDOW as u64).map(|i| block_at(i, i)).collect();
+        assert_eq!(retarget(&history, 10), 11);
+    }
+
+    #[test]
+    fn retarget_lowers_difficulty_when_blocks_come_too_slow() {
+        // 10 blocks 60s apart against a 15s/block target: blocks are coming in slow.
+        let history: Vec<Block> = (0..=RETARGET_WINDOW as u64).map(|i| block_at(i, i * 60)).collect();
+        assert_eq!(retarget(&history, 10), 9);
+    }
+
+    #[test]
+    fn retarget_keeps_difficulty_when_history_is_too_short() {
+        let history: Vec<Block> = (0..RETARGET_WINDOW as u64).map(|i| block_at(i, i)).collect();
+        assert_eq!(retarget(&history, 10), 10);
     }
 }